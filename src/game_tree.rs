@@ -0,0 +1,575 @@
+//! Mutable PTN game-record tree: a root position with a mainline that can
+//! fork into variations (sidelines), similar to the node tree used by SGF
+//! editors.
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::Move;
+
+/// A PTN move annotation, mirroring takparse's own suffix vocabulary for
+/// marking a move as good, bad, a Tak threat, or a Tinue.
+#[pyclass(eq)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Annotation {
+    #[default]
+    None,
+    /// `!`
+    Good,
+    /// `?`
+    Bad,
+    /// `!?`
+    Interesting,
+    /// `?!`
+    Doubtful,
+    /// `'`, a move that threatens a road (Tak).
+    Tak,
+    /// `''`, a move that forces a win (Tinue).
+    Tinue,
+}
+
+impl Annotation {
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Good => "!",
+            Self::Bad => "?",
+            Self::Interesting => "!?",
+            Self::Doubtful => "?!",
+            Self::Tak => "'",
+            Self::Tinue => "''",
+        }
+    }
+}
+
+#[pymethods]
+impl Annotation {
+    /// Parse a PTN annotation suffix such as `"!?"` or `"''"`.
+    #[staticmethod]
+    fn parse(suffix: &str) -> PyResult<Self> {
+        match suffix {
+            "" => Ok(Self::None),
+            "!" => Ok(Self::Good),
+            "?" => Ok(Self::Bad),
+            "!?" => Ok(Self::Interesting),
+            "?!" => Ok(Self::Doubtful),
+            "'" => Ok(Self::Tak),
+            "''" => Ok(Self::Tinue),
+            _ => Err(PyValueError::new_err("unrecognised annotation suffix")),
+        }
+    }
+
+    fn __repr__(&self) -> &'static str {
+        self.suffix()
+    }
+}
+
+/// A categorical position evaluation, mirroring the SGF good-for-white,
+/// good-for-black, even and unclear node properties.
+#[pyclass(eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Evaluation {
+    GoodForWhite,
+    GoodForBlack,
+    Even,
+    Unclear,
+}
+
+/// A single node in a [`GameTree`]: the move played to reach it, any
+/// comments, annotation and evaluation attached to it, and its child nodes
+/// (the first child is the mainline continuation, the rest are variations).
+#[pyclass]
+#[derive(Clone, Default)]
+pub struct Node {
+    #[pyo3(get)]
+    pub my_move: Option<Move>,
+    #[pyo3(get, set)]
+    pub comments: Vec<String>,
+    #[pyo3(get, set)]
+    pub annotation: Annotation,
+    #[pyo3(get, set)]
+    pub evaluation: Option<f64>,
+    #[pyo3(get, set)]
+    pub eval_category: Option<Evaluation>,
+    pub children: Vec<Node>,
+}
+
+#[pymethods]
+impl Node {
+    #[new]
+    #[pyo3(signature = (my_move=None))]
+    fn new(my_move: Option<Move>) -> Self {
+        Self {
+            my_move,
+            comments: Vec::new(),
+            annotation: Annotation::None,
+            evaluation: None,
+            eval_category: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Number of children (the mainline continuation plus any variations).
+    fn child_count(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Get the child at `index` (`0` is the mainline continuation).
+    fn child(&self, index: usize) -> PyResult<Node> {
+        self.children
+            .get(index)
+            .cloned()
+            .ok_or_else(|| PyValueError::new_err("no child at that index"))
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// A mutable PTN game record: a tree of [`Node`]s rooted at a starting
+/// position, with a cursor tracking the node currently being edited.
+///
+/// Use [`append`](GameTree::append) to add a move after the cursor,
+/// [`descend`](GameTree::descend)/[`ascend`](GameTree::ascend) to move the
+/// cursor around the tree, and [`to_ptn`](GameTree::to_ptn) to serialize the
+/// whole tree, variations included, back into PTN.
+#[pyclass]
+pub struct GameTree {
+    root: Node,
+    /// Indices of children to follow from the root to reach the current node.
+    path: Vec<usize>,
+    /// TPS of the starting position, or `None` if the tree starts from the
+    /// standard opening position.
+    #[pyo3(get)]
+    start_tps: Option<String>,
+}
+
+impl GameTree {
+    fn node_at<'a>(root: &'a Node, path: &[usize]) -> &'a Node {
+        let mut node = root;
+        for &index in path {
+            node = &node.children[index];
+        }
+        node
+    }
+
+    fn node_at_mut<'a>(root: &'a mut Node, path: &[usize]) -> &'a mut Node {
+        let mut node = root;
+        for &index in path {
+            node = &mut node.children[index];
+        }
+        node
+    }
+
+    /// Write a node's move, annotation, evaluation and comments (but not
+    /// its children).
+    fn write_move_text(node: &Node, out: &mut String) {
+        if let Some(my_move) = &node.my_move {
+            out.push_str(&my_move.__repr__());
+            out.push_str(node.annotation.suffix());
+            out.push(' ');
+        }
+        if let Some(evaluation) = node.evaluation {
+            match node.eval_category {
+                Some(category) => out.push_str(&format!("{{eval: {evaluation} {category:?}}} ")),
+                None => out.push_str(&format!("{{eval: {evaluation}}} ")),
+            }
+        }
+        for comment in &node.comments {
+            out.push_str(&format!("{{{comment}}} "));
+        }
+    }
+
+    /// Write a variation's move and its whole subtree, wrapped in `( … )`.
+    fn write_variation(node: &Node, ply: u16, out: &mut String) {
+        out.push('(');
+        if ply % 2 == 0 {
+            out.push_str(&format!("{}. ", ply / 2 + 1));
+        } else {
+            out.push_str(&format!("{}... ", ply / 2 + 1));
+        }
+        Self::write_move_text(node, out);
+        Self::write_ptn(node, ply + 1, out);
+        // Drop the trailing space before closing the variation.
+        if out.ends_with(' ') {
+            out.pop();
+        }
+        out.push_str(") ");
+    }
+
+    /// Write the mainline move at `node`'s children[0], any variations
+    /// (alternatives to that move) right after it, and then continue down
+    /// the mainline.
+    fn write_ptn(node: &Node, ply: u16, out: &mut String) {
+        let Some((mainline, variations)) = node.children.split_first() else {
+            return;
+        };
+
+        if ply % 2 == 0 {
+            out.push_str(&format!("{}. ", ply / 2 + 1));
+        }
+        Self::write_move_text(mainline, out);
+
+        for variation in variations {
+            Self::write_variation(variation, ply, out);
+        }
+
+        Self::write_ptn(mainline, ply + 1, out);
+    }
+}
+
+/// A lexical token of a PTN move list, as produced by [`GameTree::to_ptn`].
+/// Move numbers (`"1."`, `"1..."`) carry no information beyond what the tree
+/// structure already encodes, so the tokenizer drops them rather than
+/// returning them as a variant here.
+enum Token {
+    /// `(`
+    Open,
+    /// `)`
+    Close,
+    /// The text inside a `{ … }` comment, with the braces stripped.
+    Comment(String),
+    /// A move, with any trailing annotation suffix (e.g. `"a1!"`) still
+    /// attached.
+    Move(String),
+}
+
+impl GameTree {
+    /// Split a leading `[TPS "…"]` tag off `ptn`, returning its contents and
+    /// the rest of the text.
+    fn split_tps_tag(ptn: &str) -> PyResult<(Option<String>, &str)> {
+        let Some(after_prefix) = ptn.trim_start().strip_prefix("[TPS \"") else {
+            return Ok((None, ptn));
+        };
+        let end = after_prefix
+            .find("\"]")
+            .ok_or_else(|| PyValueError::new_err("unterminated TPS tag"))?;
+        Ok((
+            Some(after_prefix[..end].to_owned()),
+            &after_prefix[end + 2..],
+        ))
+    }
+
+    fn tokenize(input: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut chars = input.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            match c {
+                '(' => {
+                    chars.next();
+                    tokens.push(Token::Open);
+                }
+                ')' => {
+                    chars.next();
+                    tokens.push(Token::Close);
+                }
+                '{' => {
+                    chars.next();
+                    let mut comment = String::new();
+                    for ch in chars.by_ref() {
+                        if ch == '}' {
+                            break;
+                        }
+                        comment.push(ch);
+                    }
+                    tokens.push(Token::Comment(comment));
+                }
+                '[' => {
+                    // Skip any other bracketed header tag.
+                    for ch in chars.by_ref() {
+                        if ch == ']' {
+                            break;
+                        }
+                    }
+                }
+                c if c.is_whitespace() => {
+                    chars.next();
+                }
+                _ => {
+                    let mut word = String::new();
+                    while let Some(&ch) = chars.peek() {
+                        if ch.is_whitespace() || matches!(ch, '(' | ')' | '{' | '[') {
+                            break;
+                        }
+                        word.push(ch);
+                        chars.next();
+                    }
+                    let digits = word.trim_end_matches('.');
+                    if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+                        // A bare move number, e.g. "12." or "12...".
+                        continue;
+                    }
+                    tokens.push(Token::Move(word));
+                }
+            }
+        }
+        tokens
+    }
+
+    /// Split the trailing annotation suffix (if any) off a move token.
+    fn split_annotation(word: &str) -> (&str, Annotation) {
+        for suffix in ["!?", "?!", "''", "!", "?", "'"] {
+            if let Some(stripped) = word.strip_suffix(suffix) {
+                if let Ok(annotation) = Annotation::parse(suffix) {
+                    return (stripped, annotation);
+                }
+            }
+        }
+        (word, Annotation::None)
+    }
+
+    /// Apply a parsed `{ … }` comment to `node`: recognise `write_move_text`'s
+    /// `eval: <value> [<category>]` format and reconstruct the evaluation
+    /// fields, falling back to a plain comment otherwise.
+    fn apply_comment(node: &mut Node, text: &str) {
+        if let Some(rest) = text.strip_prefix("eval: ") {
+            let mut parts = rest.splitn(2, ' ');
+            let value = parts.next().and_then(|v| v.parse::<f64>().ok());
+            let tag = parts.next();
+            // Only treat this as one of our own eval comments if the whole
+            // text matches that exact shape; a plain user comment that
+            // happens to start with "eval: " (e.g. "eval: 5 nice game")
+            // must not be swallowed as a bogus evaluation.
+            let tag_recognised = tag.map_or(true, |tag| Evaluation::parse_tag(tag).is_some());
+            if let (Some(value), true) = (value, tag_recognised) {
+                node.evaluation = Some(value);
+                node.eval_category = tag.and_then(Evaluation::parse_tag);
+                return;
+            }
+        }
+        node.comments.push(text.to_owned());
+    }
+
+    /// Parse a single node's own move, annotation and comments (not its
+    /// children) from the token at `*position`.
+    fn parse_move_only(tokens: &[Token], position: &mut usize) -> PyResult<Option<Node>> {
+        let Some(Token::Move(word)) = tokens.get(*position) else {
+            return Ok(None);
+        };
+        *position += 1;
+
+        let (move_text, annotation) = Self::split_annotation(word);
+        let mut node = Node::new(Some(Move::new(move_text)?));
+        node.annotation = annotation;
+
+        while let Some(Token::Comment(text)) = tokens.get(*position) {
+            *position += 1;
+            Self::apply_comment(&mut node, text);
+        }
+
+        Ok(Some(node))
+    }
+
+    /// Parse a node list as written by [`write_ptn`](Self::write_ptn): the
+    /// mainline move (with its own children, parsed recursively) followed by
+    /// any variations (each with their own children, parsed recursively from
+    /// inside their parens). Mirrors `write_ptn`/`write_variation` exactly,
+    /// so it is the inverse of the pair of them.
+    fn parse_children(tokens: &[Token], position: &mut usize) -> PyResult<Vec<Node>> {
+        let Some(mut mainline) = Self::parse_move_only(tokens, position)? else {
+            return Ok(Vec::new());
+        };
+
+        let mut variations = Vec::new();
+        while let Some(Token::Open) = tokens.get(*position) {
+            *position += 1;
+            let mut variation = Self::parse_move_only(tokens, position)?
+                .ok_or_else(|| PyValueError::new_err("empty variation"))?;
+            variation.children = Self::parse_children(tokens, position)?;
+            variations.push(variation);
+            match tokens.get(*position) {
+                Some(Token::Close) => *position += 1,
+                _ => return Err(PyValueError::new_err("unterminated variation")),
+            }
+        }
+
+        mainline.children = Self::parse_children(tokens, position)?;
+
+        let mut result = vec![mainline];
+        result.extend(variations);
+        Ok(result)
+    }
+}
+
+impl Evaluation {
+    /// Parse the `{category:?}` tag written by `write_move_text`.
+    fn parse_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "GoodForWhite" => Some(Self::GoodForWhite),
+            "GoodForBlack" => Some(Self::GoodForBlack),
+            "Even" => Some(Self::Even),
+            "Unclear" => Some(Self::Unclear),
+            _ => None,
+        }
+    }
+}
+
+#[pymethods]
+impl GameTree {
+    /// Create a new game tree. If `tps` is given the tree starts from that
+    /// position, otherwise it starts from the standard opening position.
+    #[new]
+    #[pyo3(signature = (tps=None))]
+    fn new(tps: Option<String>) -> Self {
+        Self {
+            root: Node::default(),
+            path: Vec::new(),
+            start_tps: tps,
+        }
+    }
+
+    /// Parse a PTN move list, as produced by [`to_ptn`](Self::to_ptn), back
+    /// into a tree: moves, variations, annotations, evaluations and comments
+    /// are all reconstructed (an evaluation category only where `to_ptn`
+    /// wrote it), including any comments/evaluation attached to
+    /// the root itself (written before the first move). A leading
+    /// `[TPS "…"]` tag is recognised and recorded as the starting position.
+    #[staticmethod]
+    fn from_ptn(ptn: &str) -> PyResult<Self> {
+        let (start_tps, rest) = Self::split_tps_tag(ptn)?;
+        let tokens = Self::tokenize(rest);
+        let mut position = 0;
+
+        let mut root = Node::default();
+        while let Some(Token::Comment(text)) = tokens.get(position) {
+            position += 1;
+            Self::apply_comment(&mut root, text);
+        }
+        root.children = Self::parse_children(&tokens, &mut position)?;
+
+        Ok(Self {
+            root,
+            path: Vec::new(),
+            start_tps,
+        })
+    }
+
+    /// Append `my_move` as a child of the current node and descend into it.
+    /// If the current node already has children this becomes a new
+    /// variation (sideline) rather than replacing the mainline.
+    fn append(&mut self, my_move: Move) -> usize {
+        let current = Self::node_at_mut(&mut self.root, &self.path);
+        current.children.push(Node::new(Some(my_move)));
+        let index = current.children.len() - 1;
+        self.path.push(index);
+        index
+    }
+
+    /// Move the cursor to the child at `index`.
+    fn descend(&mut self, index: usize) -> PyResult<()> {
+        let current = Self::node_at(&self.root, &self.path);
+        if index >= current.children.len() {
+            return Err(PyValueError::new_err("no child at that index"));
+        }
+        self.path.push(index);
+        Ok(())
+    }
+
+    /// Move the cursor to the parent of the current node.
+    fn ascend(&mut self) -> PyResult<()> {
+        if self.path.pop().is_none() {
+            return Err(PyValueError::new_err("already at the root"));
+        }
+        Ok(())
+    }
+
+    /// Move the cursor back to the root.
+    fn go_to_root(&mut self) {
+        self.path.clear();
+    }
+
+    /// Promote the variation at `index` under the current node so it
+    /// becomes the mainline continuation (swaps it with child `0`).
+    fn promote(&mut self, index: usize) -> PyResult<()> {
+        let current = Self::node_at_mut(&mut self.root, &self.path);
+        if index >= current.children.len() {
+            return Err(PyValueError::new_err("no child at that index"));
+        }
+        current.children.swap(0, index);
+        Ok(())
+    }
+
+    /// The node the cursor is currently on.
+    fn current(&self) -> Node {
+        Self::node_at(&self.root, &self.path).clone()
+    }
+
+    /// Add a comment to the current node.
+    fn add_comment(&mut self, comment: String) {
+        Self::node_at_mut(&mut self.root, &self.path)
+            .comments
+            .push(comment);
+    }
+
+    /// Set the annotation on the current node.
+    fn set_annotation(&mut self, annotation: Annotation) {
+        Self::node_at_mut(&mut self.root, &self.path).annotation = annotation;
+    }
+
+    /// Set the evaluation on the current node.
+    fn set_evaluation(&mut self, evaluation: Option<f64>) {
+        Self::node_at_mut(&mut self.root, &self.path).evaluation = evaluation;
+    }
+
+    /// Set the evaluation category on the current node.
+    fn set_eval_category(&mut self, eval_category: Option<Evaluation>) {
+        Self::node_at_mut(&mut self.root, &self.path).eval_category = eval_category;
+    }
+
+    /// Serialize the whole tree, including variations, to PTN. If the tree
+    /// was created from a non-standard starting position, a `[TPS "…"]` tag
+    /// is emitted first so the move list replays from the right position.
+    /// Comments and an evaluation attached to the root itself (before any
+    /// move has been played) are emitted first, mirroring how
+    /// [`from_ptn`](Self::from_ptn) reads them back. An evaluation category
+    /// is written only together with a numeric evaluation; a category on a
+    /// node without one is not serialized.
+    fn to_ptn(&self) -> String {
+        let mut out = String::new();
+        if let Some(tps) = &self.start_tps {
+            out.push_str(&format!("[TPS \"{tps}\"]\n\n"));
+        }
+        Self::write_move_text(&self.root, &mut out);
+        Self::write_ptn(&self.root, 0, &mut out);
+        out.trim_end().to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_ptn_from_ptn_round_trips_moves_variations_and_annotations() {
+        let mut tree = GameTree::new(None);
+        tree.add_comment("opening thoughts".to_owned());
+        tree.set_evaluation(Some(0.25));
+        tree.set_eval_category(Some(Evaluation::Even));
+
+        tree.append(Move::new("a1").unwrap());
+        tree.set_annotation(Annotation::Good);
+        tree.add_comment("solid start".to_owned());
+
+        tree.append(Move::new("a2").unwrap());
+        tree.set_evaluation(Some(-1.5));
+        tree.set_eval_category(Some(Evaluation::GoodForBlack));
+
+        tree.ascend().unwrap();
+        // A second move from the same node becomes a variation rather than
+        // replacing the mainline continuation (a2).
+        tree.append(Move::new("b2").unwrap());
+        tree.set_annotation(Annotation::Tinue);
+
+        let ptn = tree.to_ptn();
+        let round_tripped = GameTree::from_ptn(&ptn).unwrap();
+        assert_eq!(round_tripped.to_ptn(), ptn);
+    }
+
+    #[test]
+    fn to_ptn_from_ptn_round_trips_the_starting_tps() {
+        let tps = "x5/x5/x5/x5/x5 1 1".to_owned();
+        let tree = GameTree::new(Some(tps.clone()));
+        let round_tripped = GameTree::from_ptn(&tree.to_ptn()).unwrap();
+        assert_eq!(round_tripped.start_tps, Some(tps));
+    }
+}
@@ -3,6 +3,9 @@ use std::hash::{Hash, Hasher};
 use fast_tak::Symmetry;
 use pyo3::{exceptions::PyValueError, prelude::*};
 
+mod game_tree;
+use game_tree::{Annotation, Evaluation, GameTree, Node};
+
 macro_rules! game {
     ($name:ident, $size:literal, $half_komi:literal) => {
         mod $name {
@@ -10,21 +13,99 @@ macro_rules! game {
 
             #[pyclass]
             #[derive(Clone, Default)]
-            pub struct Game(pub fast_tak::Game<$size, $half_komi>);
+            pub struct Game {
+                inner: fast_tak::Game<$size, $half_komi>,
+                history: Vec<fast_tak::takparse::Move>,
+                /// Positions before each played move, for [`undo`](Game::undo).
+                snapshots: Vec<fast_tak::Game<$size, $half_komi>>,
+            }
+
+            impl Game {
+                pub(crate) fn from_inner(inner: fast_tak::Game<$size, $half_komi>) -> Self {
+                    Self {
+                        inner,
+                        ..Default::default()
+                    }
+                }
+
+                /// Check that the reserves are consistent with the stones
+                /// on the board: a color may never have more total pieces
+                /// (on the board plus in reserve) than it started with, and
+                /// no more of those may be capstones than the board size's
+                /// starting reserve holds.
+                fn validate(&self) -> PyResult<()> {
+                    let default = fast_tak::Game::<$size, $half_komi>::default();
+                    let max_white_total = u32::from(default.white_reserves.stones)
+                        + u32::from(default.white_reserves.caps);
+                    let max_black_total = u32::from(default.black_reserves.stones)
+                        + u32::from(default.black_reserves.caps);
+                    let max_white_caps = default.white_reserves.caps;
+                    let max_black_caps = default.black_reserves.caps;
+
+                    let mut white_on_board = 0u32;
+                    let mut black_on_board = 0u32;
+                    let mut white_caps_on_board = 0u8;
+                    let mut black_caps_on_board = 0u8;
+
+                    for row in &self.inner.board {
+                        for stack in row {
+                            for color in stack.colors() {
+                                match Color::from(color) {
+                                    Color::White => white_on_board += 1,
+                                    Color::Black => black_on_board += 1,
+                                }
+                            }
+                            if let Some((piece, color)) = stack.top() {
+                                if matches!(Piece::from(piece), Piece::Cap) {
+                                    match Color::from(color) {
+                                        Color::White => white_caps_on_board += 1,
+                                        Color::Black => black_caps_on_board += 1,
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    if white_caps_on_board.saturating_add(self.inner.white_reserves.caps)
+                        > max_white_caps
+                        || black_caps_on_board.saturating_add(self.inner.black_reserves.caps)
+                            > max_black_caps
+                    {
+                        return Err(PyValueError::new_err(
+                            "too many capstones for this board size",
+                        ));
+                    }
+
+                    let white_reserve_total = u32::from(self.inner.white_reserves.stones)
+                        + u32::from(self.inner.white_reserves.caps);
+                    let black_reserve_total = u32::from(self.inner.black_reserves.stones)
+                        + u32::from(self.inner.black_reserves.caps);
+
+                    if white_on_board + white_reserve_total > max_white_total
+                        || black_on_board + black_reserve_total > max_black_total
+                    {
+                        return Err(PyValueError::new_err(
+                            "reserve counts are inconsistent with the stones on the board",
+                        ));
+                    }
+
+                    Ok(())
+                }
+            }
 
             #[pymethods]
             impl Game {
                 fn __repr__(&self) -> String {
-                    fast_tak::takparse::Tps::from(self.0.clone()).to_string()
+                    fast_tak::takparse::Tps::from(self.inner.clone()).to_string()
                 }
 
                 fn __eq__(&self, other: Game) -> bool {
-                    self.0 == other.0
+                    self.inner == other.inner
                 }
 
                 fn __hash__(&self) -> u64 {
                     let mut s = std::hash::DefaultHasher::new();
-                    self.0.hash(&mut s);
+                    self.inner.hash(&mut s);
                     s.finish()
                 }
 
@@ -41,24 +122,50 @@ macro_rules! game {
                 /// Get the moves possible in the current position.
                 fn possible_moves(&self) -> Vec<Move> {
                     let mut moves = Vec::with_capacity(128);
-                    self.0.possible_moves(&mut moves);
+                    self.inner.possible_moves(&mut moves);
                     moves.into_iter().map(Move).collect()
                 }
 
                 /// Play a move.
                 fn play(&mut self, my_move: Move) -> Result<(), PlayError> {
-                    self.0.play(my_move.0).map_err(Into::into)
+                    let snapshot = self.inner.clone();
+                    self.inner.play(my_move.0)?;
+                    self.snapshots.push(snapshot);
+                    self.history.push(my_move.0);
+                    Ok(())
+                }
+
+                /// Undo the last move played, restoring the previous board,
+                /// reserves, side to move, ply and reversible-ply count.
+                fn undo(&mut self) -> PyResult<()> {
+                    let previous = self
+                        .snapshots
+                        .pop()
+                        .ok_or_else(|| PyValueError::new_err("no moves to undo"))?;
+                    self.inner = previous;
+                    self.history.pop();
+                    Ok(())
+                }
+
+                /// Whether there is a move to [`undo`](Self::undo).
+                fn can_undo(&self) -> bool {
+                    !self.snapshots.is_empty()
+                }
+
+                /// The moves played so far, oldest first.
+                fn history(&self) -> Vec<Move> {
+                    self.history.iter().map(|&my_move| Move(my_move)).collect()
                 }
 
                 /// Check whether the game has ended and who as won.
                 fn result(&self) -> GameResult {
-                    self.0.result().into()
+                    self.inner.result().into()
                 }
 
                 /// Get the board.
                 fn board(&self) -> Vec<Vec<Option<(Piece, Vec<Color>)>>> {
                     // TODO: Can we avoid all these ugly allocations?
-                    self.0
+                    self.inner
                         .board
                         .iter()
                         .map(|row| {
@@ -75,29 +182,137 @@ macro_rules! game {
                         .collect()
                 }
 
+                /// Place a stack of pieces on a square, replacing whatever
+                /// was there. The first entry is the bottom of the stack,
+                /// the last is the top.
+                fn set_square(
+                    &mut self,
+                    row: usize,
+                    col: usize,
+                    stack: Vec<(Piece, Color)>,
+                ) -> PyResult<()> {
+                    if row >= $size || col >= $size {
+                        return Err(PyValueError::new_err("square out of bounds"));
+                    }
+                    // A capstone can never legally end up buried (nothing can
+                    // ever be placed or moved on top of it), so `validate`'s
+                    // board-wide cap count only ever looks at each square's
+                    // top piece. Reject a buried capstone here, where the
+                    // whole stack is still available to check.
+                    if stack
+                        .iter()
+                        .rev()
+                        .skip(1)
+                        .any(|(piece, _)| matches!(piece, Piece::Cap))
+                    {
+                        return Err(PyValueError::new_err(
+                            "a capstone can only be the top piece of a stack",
+                        ));
+                    }
+                    let previous = self.inner.board[row][col].clone();
+                    let cell = &mut self.inner.board[row][col];
+                    *cell = Default::default();
+                    for (piece, color) in stack {
+                        cell.push(piece.into(), color.into());
+                    }
+                    if let Err(error) = self.validate() {
+                        self.inner.board[row][col] = previous;
+                        return Err(error);
+                    }
+                    Ok(())
+                }
+
+                /// Remove all pieces from a square.
+                fn clear_square(&mut self, row: usize, col: usize) -> PyResult<()> {
+                    if row >= $size || col >= $size {
+                        return Err(PyValueError::new_err("square out of bounds"));
+                    }
+                    let previous = self.inner.board[row][col].clone();
+                    self.inner.board[row][col] = Default::default();
+                    if let Err(error) = self.validate() {
+                        self.inner.board[row][col] = previous;
+                        return Err(error);
+                    }
+                    Ok(())
+                }
+
                 #[getter]
                 fn to_move(&self) -> Color {
-                    self.0.to_move.into()
+                    self.inner.to_move.into()
+                }
+
+                #[setter]
+                fn set_to_move(&mut self, color: Color) {
+                    self.inner.to_move = color.into();
                 }
 
                 #[getter]
                 fn white_reserves(&self) -> (u8, u8) {
-                    (self.0.white_reserves.stones, self.0.white_reserves.caps)
+                    (
+                        self.inner.white_reserves.stones,
+                        self.inner.white_reserves.caps,
+                    )
+                }
+
+                #[setter]
+                fn set_white_reserves(&mut self, reserves: (u8, u8)) -> PyResult<()> {
+                    let previous = (
+                        self.inner.white_reserves.stones,
+                        self.inner.white_reserves.caps,
+                    );
+                    let (stones, caps) = reserves;
+                    self.inner.white_reserves.stones = stones;
+                    self.inner.white_reserves.caps = caps;
+                    if let Err(error) = self.validate() {
+                        (
+                            self.inner.white_reserves.stones,
+                            self.inner.white_reserves.caps,
+                        ) = previous;
+                        return Err(error);
+                    }
+                    Ok(())
                 }
 
                 #[getter]
                 fn black_reserves(&self) -> (u8, u8) {
-                    (self.0.black_reserves.stones, self.0.black_reserves.caps)
+                    (
+                        self.inner.black_reserves.stones,
+                        self.inner.black_reserves.caps,
+                    )
+                }
+
+                #[setter]
+                fn set_black_reserves(&mut self, reserves: (u8, u8)) -> PyResult<()> {
+                    let previous = (
+                        self.inner.black_reserves.stones,
+                        self.inner.black_reserves.caps,
+                    );
+                    let (stones, caps) = reserves;
+                    self.inner.black_reserves.stones = stones;
+                    self.inner.black_reserves.caps = caps;
+                    if let Err(error) = self.validate() {
+                        (
+                            self.inner.black_reserves.stones,
+                            self.inner.black_reserves.caps,
+                        ) = previous;
+                        return Err(error);
+                    }
+                    Ok(())
                 }
 
                 #[getter]
                 fn ply(&self) -> u16 {
-                    self.0.ply
+                    self.inner.ply
+                }
+
+                #[setter]
+                fn set_ply(&mut self, ply: u16) {
+                    self.inner.ply = ply;
                 }
 
                 #[getter]
                 fn reversible_plies(&self) -> u16 {
-                    self.0.reversible_plies
+                    self.inner.reversible_plies
                 }
 
                 fn clone(&self) -> Game {
@@ -107,18 +322,352 @@ macro_rules! game {
                 /// Clone the game and play a move in it, returning the newly created game.
                 fn clone_and_play(&self, my_move: Move) -> Result<Game, PlayError> {
                     let mut clone = Clone::clone(self);
-                    clone.0.play(my_move.0)?;
+                    clone.play(my_move)?;
                     Ok(clone)
                 }
 
                 /// Return all the symmetries of this position.
                 fn symmetries(&self) -> [Self; 8] {
-                    self.0.symmetries().map(Self)
+                    self.inner.symmetries().map(Self::from_inner)
                 }
 
                 /// Return the position in the canonical orientation.
                 fn canonical(&self) -> Self {
-                    Self(self.0.clone().canonical())
+                    Self::from_inner(self.inner.clone().canonical())
+                }
+
+                /// Count the number of leaf positions reachable in exactly
+                /// `depth` plies. The standard move-generator correctness
+                /// and throughput benchmark.
+                fn perft(&self, py: Python, depth: u32) -> u64 {
+                    py.allow_threads(|| {
+                        let mut cache = std::collections::HashMap::new();
+                        Self::perft_recurse(&self.inner, depth, &mut cache)
+                    })
+                }
+
+                /// Encode the position as stacked feature planes suitable
+                /// for feeding a neural network, shape `[channels, size,
+                /// size]`. Planes: flat/wall/cap on top per color (6),
+                /// stack height (1), ownership of the top [`TOP_N`](Self)
+                /// stones per square (8), side to move (1), white and black
+                /// reserves (4), and komi (1).
+                fn to_tensor(&self) -> Vec<Vec<Vec<f32>>> {
+                    const TOP_N: usize = 8;
+                    let channels = 6 + 1 + TOP_N + 1 + 4 + 1;
+                    let mut planes = vec![vec![vec![0f32; $size]; $size]; channels];
+
+                    for row in 0..$size {
+                        for col in 0..$size {
+                            let stack = &self.inner.board[row][col];
+                            if let Some((piece, color)) = stack.top() {
+                                let piece_index = match Piece::from(piece) {
+                                    Piece::Flat => 0,
+                                    Piece::Wall => 1,
+                                    Piece::Cap => 2,
+                                };
+                                let color_offset = match Color::from(color) {
+                                    Color::White => 0,
+                                    Color::Black => 3,
+                                };
+                                planes[color_offset + piece_index][row][col] = 1.0;
+                            }
+
+                            // `colors()` is bottom-first (see `set_square`'s
+                            // "first entry is the bottom" convention), so walk
+                            // it in reverse to get the top `TOP_N` stones.
+                            let colors = stack.colors();
+                            planes[6][row][col] = colors.len() as f32;
+                            for (depth, color) in colors.into_iter().rev().take(TOP_N).enumerate() {
+                                planes[7 + depth][row][col] = match Color::from(color) {
+                                    Color::White => 1.0,
+                                    Color::Black => -1.0,
+                                };
+                            }
+                        }
+                    }
+
+                    let side_to_move_plane = 7 + TOP_N;
+                    let side_to_move_value = match Color::from(self.inner.to_move) {
+                        Color::White => 1.0,
+                        Color::Black => -1.0,
+                    };
+                    for row in &mut planes[side_to_move_plane] {
+                        row.fill(side_to_move_value);
+                    }
+
+                    let reserve_planes = [
+                        (
+                            side_to_move_plane + 1,
+                            self.inner.white_reserves.stones as f32,
+                        ),
+                        (
+                            side_to_move_plane + 2,
+                            self.inner.white_reserves.caps as f32,
+                        ),
+                        (
+                            side_to_move_plane + 3,
+                            self.inner.black_reserves.stones as f32,
+                        ),
+                        (
+                            side_to_move_plane + 4,
+                            self.inner.black_reserves.caps as f32,
+                        ),
+                    ];
+                    for (plane, value) in reserve_planes {
+                        for row in &mut planes[plane] {
+                            row.fill(value);
+                        }
+                    }
+
+                    let komi_plane = side_to_move_plane + 5;
+                    for row in &mut planes[komi_plane] {
+                        row.fill($half_komi as f32 / 2.0);
+                    }
+
+                    planes
+                }
+
+                /// Map a move to an index into a fixed-size policy head.
+                /// Spreads are bucketed by `(square, direction, number of
+                /// squares dropped on)`; the number of stones carried and
+                /// the exact drop distribution are not encoded.
+                fn move_to_policy_index(&self, my_move: Move) -> usize {
+                    Self::policy_index_of(my_move.0)
+                }
+
+                /// Inverse of [`move_to_policy_index`](Self::move_to_policy_index).
+                /// For spreads this reconstructs a canonical move that
+                /// carries that many stones, dropping one per square.
+                fn policy_index_to_move(&self, index: usize) -> PyResult<Move> {
+                    Self::move_of_policy_index(index)
+                        .ok_or_else(|| PyValueError::new_err("policy index out of range"))
+                }
+
+                /// Size of the fixed policy head for this board size.
+                #[staticmethod]
+                fn policy_size() -> usize {
+                    let squares = $size * $size;
+                    squares * 3 + squares * 4 * $size
+                }
+
+                /// Augment a `(tensor, policy index)` training example
+                /// across all 8 board symmetries. Reuses
+                /// [`symmetries`](Self::symmetries) for the board and
+                /// [`Move::symmetries`] for `played_move`, so each returned
+                /// tensor is paired with the policy index of the same move
+                /// under the same symmetry.
+                fn augmented_tensors(
+                    &self,
+                    played_move: Move,
+                ) -> PyResult<Vec<(Vec<Vec<Vec<f32>>>, usize)>> {
+                    let move_symmetries = played_move.symmetries($size)?;
+                    Ok(self
+                        .symmetries()
+                        .into_iter()
+                        .zip(move_symmetries)
+                        .map(|(game, my_move)| {
+                            (game.to_tensor(), game.move_to_policy_index(my_move))
+                        })
+                        .collect())
+                }
+
+                /// Like [`perft`](Self::perft), but broken down by root move.
+                fn perft_divide(
+                    &self,
+                    py: Python,
+                    depth: u32,
+                ) -> std::collections::HashMap<Move, u64> {
+                    py.allow_threads(|| {
+                        let mut cache = std::collections::HashMap::new();
+                        let mut moves = Vec::with_capacity(128);
+                        self.inner.possible_moves(&mut moves);
+                        moves
+                            .into_iter()
+                            .map(|my_move| {
+                                let mut clone = self.inner.clone();
+                                clone.play(my_move).expect("possible move is legal");
+                                let count = Self::perft_recurse(
+                                    &clone,
+                                    depth.saturating_sub(1),
+                                    &mut cache,
+                                );
+                                (Move(my_move), count)
+                            })
+                            .collect()
+                    })
+                }
+            }
+
+            impl Game {
+                fn perft_recurse(
+                    game: &fast_tak::Game<$size, $half_komi>,
+                    depth: u32,
+                    cache: &mut std::collections::HashMap<
+                        (fast_tak::Game<$size, $half_komi>, u32),
+                        u64,
+                    >,
+                ) -> u64 {
+                    if depth == 0 || !matches!(game.result(), fast_tak::GameResult::Ongoing) {
+                        return 1;
+                    }
+
+                    // Keyed on the canonical position itself (not just a
+                    // 64-bit hash of it) so `HashMap` verifies full equality
+                    // on a hash collision instead of conflating two distinct
+                    // positions into the same cache entry.
+                    let key = (game.clone().canonical(), depth);
+                    if let Some(&count) = cache.get(&key) {
+                        return count;
+                    }
+
+                    let mut moves = Vec::with_capacity(128);
+                    game.possible_moves(&mut moves);
+                    let count = moves
+                        .into_iter()
+                        .map(|my_move| {
+                            let mut clone = game.clone();
+                            clone.play(my_move).expect("possible move is legal");
+                            Self::perft_recurse(&clone, depth - 1, cache)
+                        })
+                        .sum();
+
+                    cache.insert(key, count);
+                    count
+                }
+
+                fn policy_index_of(my_move: fast_tak::takparse::Move) -> usize {
+                    use fast_tak::takparse::MoveKind;
+
+                    let square = my_move.square();
+                    let square_index = square.row() as usize * $size + square.column() as usize;
+                    let squares = $size * $size;
+
+                    match my_move.kind() {
+                        MoveKind::Place(piece) => {
+                            let kind_index = match Piece::from(piece) {
+                                Piece::Flat => 0,
+                                Piece::Wall => 1,
+                                Piece::Cap => 2,
+                            };
+                            kind_index * squares + square_index
+                        }
+                        MoveKind::Spread(direction, pattern) => {
+                            let direction_index = match Direction::from(direction) {
+                                Direction::Up => 0,
+                                Direction::Down => 1,
+                                Direction::Left => 2,
+                                Direction::Right => 3,
+                            };
+                            let carry = pattern.into_iter().count().clamp(1, $size) - 1;
+                            3 * squares
+                                + direction_index * squares * $size
+                                + square_index * $size
+                                + carry
+                        }
+                    }
+                }
+
+                fn move_of_policy_index(index: usize) -> Option<Move> {
+                    let squares = $size * $size;
+
+                    let (square_index, kind_tag) = if index < 3 * squares {
+                        (index % squares, index / squares)
+                    } else {
+                        let rest = index - 3 * squares;
+                        if rest >= 4 * squares * $size {
+                            return None;
+                        }
+                        (rest / $size % squares, 3 + rest / (squares * $size))
+                    };
+
+                    let row = (square_index / $size) as u8;
+                    let col = (square_index % $size) as u8;
+                    let square = format!("{}{}", char::from(b'a' + col), row + 1);
+
+                    let text = match kind_tag {
+                        0 => square,
+                        1 => format!("S{square}"),
+                        2 => format!("C{square}"),
+                        direction_index @ 3..=6 => {
+                            let rest = index - 3 * squares;
+                            let symbol = match direction_index - 3 {
+                                0 => '+',
+                                1 => '-',
+                                2 => '<',
+                                _ => '>',
+                            };
+                            // A slot that drops on more squares than the
+                            // square has room for in that direction would
+                            // leave the board, so it decodes to no move. A
+                            // square against the edge it spreads towards has
+                            // no legal move at all for this slot.
+                            let max_carry = match symbol {
+                                '+' => $size - 1 - row as usize,
+                                '-' => row as usize,
+                                '<' => col as usize,
+                                _ => $size - 1 - col as usize,
+                            };
+                            if max_carry == 0 {
+                                return None;
+                            }
+                            let carry = rest % $size + 1;
+                            if carry > max_carry {
+                                return None;
+                            }
+                            let drops = "1".repeat(carry);
+                            if carry == 1 {
+                                format!("{square}{symbol}")
+                            } else {
+                                format!("{carry}{square}{symbol}{drops}")
+                            }
+                        }
+                        _ => return None,
+                    };
+
+                    text.parse().ok().map(Move)
+                }
+            }
+
+            #[cfg(test)]
+            mod tests {
+                use super::*;
+
+                // On the first move of the game each player must place a
+                // flat stone of the opponent's color, so from an empty
+                // board perft(1) is exactly the number of squares, and
+                // perft(2) is every ordered pair of distinct squares (e.g.
+                // the well-known 5x5 perft(2) == 600 is 25 * 24).
+                #[test]
+                fn perft_matches_known_opening_counts() {
+                    let game = Game::default();
+                    let mut cache = std::collections::HashMap::new();
+                    let squares = ($size * $size) as u64;
+
+                    assert_eq!(Game::perft_recurse(&game.inner, 0, &mut cache), 1);
+                    assert_eq!(Game::perft_recurse(&game.inner, 1, &mut cache), squares);
+                    assert_eq!(
+                        Game::perft_recurse(&game.inner, 2, &mut cache),
+                        squares * (squares - 1)
+                    );
+                }
+
+                // For every index in the fixed-size policy head, decoding
+                // and re-encoding it must either return the same index
+                // back, or the index must fall in the spread region and be
+                // one of the documented off-board holes (see
+                // `move_of_policy_index`).
+                #[test]
+                fn policy_index_round_trips_or_is_a_documented_hole() {
+                    let squares = $size * $size;
+                    for index in 0..Game::policy_size() {
+                        match Game::move_of_policy_index(index) {
+                            Some(my_move) => {
+                                assert_eq!(Game::policy_index_of(my_move.0), index);
+                            }
+                            None => assert!(index >= 3 * squares),
+                        }
+                    }
                 }
             }
         }
@@ -164,18 +713,18 @@ fn new_game(py: Python, size: usize, half_komi: i8) -> PyResult<PyObject> {
 fn game_from_tps(py: Python, size: usize, tps: &str, half_komi: i8) -> PyResult<PyObject> {
     let tps: fast_tak::takparse::Tps = tps.parse().map_err(Into::<ParseTpsError>::into)?;
     match (size, half_komi) {
-        (3, 0) => Ok(size_3::Game(tps.into()).into_py(py)),
-        (4, 0) => Ok(size_4::Game(tps.into()).into_py(py)),
-        (5, 0) => Ok(size_5::Game(tps.into()).into_py(py)),
-        (6, 0) => Ok(size_6::Game(tps.into()).into_py(py)),
-        (7, 0) => Ok(size_7::Game(tps.into()).into_py(py)),
-        (8, 0) => Ok(size_8::Game(tps.into()).into_py(py)),
-        (3, 4) => Ok(size_3_half_komi_4::Game(tps.into()).into_py(py)),
-        (4, 4) => Ok(size_4_half_komi_4::Game(tps.into()).into_py(py)),
-        (5, 4) => Ok(size_5_half_komi_4::Game(tps.into()).into_py(py)),
-        (6, 4) => Ok(size_6_half_komi_4::Game(tps.into()).into_py(py)),
-        (7, 4) => Ok(size_7_half_komi_4::Game(tps.into()).into_py(py)),
-        (8, 4) => Ok(size_8_half_komi_4::Game(tps.into()).into_py(py)),
+        (3, 0) => Ok(size_3::Game::from_inner(tps.into()).into_py(py)),
+        (4, 0) => Ok(size_4::Game::from_inner(tps.into()).into_py(py)),
+        (5, 0) => Ok(size_5::Game::from_inner(tps.into()).into_py(py)),
+        (6, 0) => Ok(size_6::Game::from_inner(tps.into()).into_py(py)),
+        (7, 0) => Ok(size_7::Game::from_inner(tps.into()).into_py(py)),
+        (8, 0) => Ok(size_8::Game::from_inner(tps.into()).into_py(py)),
+        (3, 4) => Ok(size_3_half_komi_4::Game::from_inner(tps.into()).into_py(py)),
+        (4, 4) => Ok(size_4_half_komi_4::Game::from_inner(tps.into()).into_py(py)),
+        (5, 4) => Ok(size_5_half_komi_4::Game::from_inner(tps.into()).into_py(py)),
+        (6, 4) => Ok(size_6_half_komi_4::Game::from_inner(tps.into()).into_py(py)),
+        (7, 4) => Ok(size_7_half_komi_4::Game::from_inner(tps.into()).into_py(py)),
+        (8, 4) => Ok(size_8_half_komi_4::Game::from_inner(tps.into()).into_py(py)),
         _ => Err(PyValueError::new_err("Unsupported size or komi")),
     }
 }
@@ -199,6 +748,20 @@ impl From<fast_tak::takparse::ParseTpsError> for ParseTpsError {
 #[derive(Clone)]
 struct Move(fast_tak::takparse::Move);
 
+impl PartialEq for Move {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Move {}
+
+impl Hash for Move {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
 #[pymethods]
 impl Move {
     fn __repr__(&self) -> String {
@@ -381,6 +944,15 @@ impl From<fast_tak::takparse::Color> for Color {
     }
 }
 
+impl From<Color> for fast_tak::takparse::Color {
+    fn from(value: Color) -> Self {
+        match value {
+            Color::White => Self::White,
+            Color::Black => Self::Black,
+        }
+    }
+}
+
 #[pyclass]
 enum Piece {
     Flat,
@@ -399,6 +971,16 @@ impl From<fast_tak::takparse::Piece> for Piece {
     }
 }
 
+impl From<Piece> for fast_tak::takparse::Piece {
+    fn from(value: Piece) -> Self {
+        match value {
+            Piece::Flat => Self::Flat,
+            Piece::Wall => Self::Wall,
+            Piece::Cap => Self::Cap,
+        }
+    }
+}
+
 #[pyclass]
 struct PlayError(fast_tak::PlayError);
 
@@ -491,6 +1073,10 @@ fn takpy(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Direction>()?;
     m.add_class::<MoveKind>()?;
     m.add_class::<Ptn>()?;
+    m.add_class::<GameTree>()?;
+    m.add_class::<Node>()?;
+    m.add_class::<Annotation>()?;
+    m.add_class::<Evaluation>()?;
     m.add_class::<PlayError>()?;
     m.add_class::<ParseMoveError>()?;
     m.add_class::<ParseTpsError>()?;